@@ -0,0 +1,92 @@
+//! In-memory Bloom filter used to pre-screen hashes before hitting SQLite.
+//!
+//! Sized from the expected number of items and a target false-positive rate,
+//! using the standard `m = -n*ln(p)/(ln2)^2`, `k = (m/n)*ln2` formulas. Bit
+//! positions are derived via double hashing (`h1 + i*h2 mod m`) from two
+//! independent FNV-1a passes over the hash hex string, so we only need two
+//! hash computations per lookup regardless of `k`.
+
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+// Second pass uses a different offset basis so h1/h2 are independent.
+const FNV_OFFSET_BASIS_2: u64 = 0x84222325cbf29ce4;
+
+fn fnv1a_64(data: &[u8], offset_basis: u64) -> u64 {
+    let mut hash = offset_basis;
+    for &byte in data {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+impl BloomFilter {
+    /// Size a filter for `expected_items` entries at the given target
+    /// false-positive rate (e.g. `1e-6`).
+    pub fn new(expected_items: u64, target_fp_rate: f64) -> Self {
+        let expected_items = expected_items.max(1);
+        let num_bits = Self::optimal_num_bits(expected_items, target_fp_rate);
+        let num_hashes = Self::optimal_num_hashes(num_bits, expected_items);
+
+        BloomFilter {
+            bits: vec![0u64; (num_bits as usize / 64) + 1],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    fn optimal_num_bits(n: u64, p: f64) -> u64 {
+        let n = n as f64;
+        let m = -(n * p.ln()) / (std::f64::consts::LN_2 * std::f64::consts::LN_2);
+        m.ceil().max(64.0) as u64
+    }
+
+    fn optimal_num_hashes(m: u64, n: u64) -> u32 {
+        let k = (m as f64 / n as f64) * std::f64::consts::LN_2;
+        k.round().max(1.0) as u32
+    }
+
+    fn bit_indices(&self, hash_hex: &str) -> (u64, u64) {
+        let bytes = hash_hex.as_bytes();
+        let h1 = fnv1a_64(bytes, FNV_OFFSET_BASIS);
+        let h2 = fnv1a_64(bytes, FNV_OFFSET_BASIS_2);
+        (h1, h2)
+    }
+
+    fn set_bit(&mut self, index: u64) {
+        let idx = (index % self.num_bits) as usize;
+        self.bits[idx / 64] |= 1u64 << (idx % 64);
+    }
+
+    fn get_bit(&self, index: u64) -> bool {
+        let idx = (index % self.num_bits) as usize;
+        (self.bits[idx / 64] & (1u64 << (idx % 64))) != 0
+    }
+
+    /// Insert a hash hex string (sha1 or md5) into the filter.
+    pub fn insert_hash(&mut self, hash_hex: &str) {
+        let (h1, h2) = self.bit_indices(hash_hex);
+        for i in 0..self.num_hashes as u64 {
+            self.set_bit(h1.wrapping_add(i.wrapping_mul(h2)));
+        }
+    }
+
+    /// Test whether a hash hex string may be present. `false` is a
+    /// definitive miss; `true` may be a false positive and must be
+    /// confirmed against the source of truth.
+    pub fn contains_hash(&self, hash_hex: &str) -> bool {
+        let (h1, h2) = self.bit_indices(hash_hex);
+        for i in 0..self.num_hashes as u64 {
+            if !self.get_bit(h1.wrapping_add(i.wrapping_mul(h2))) {
+                return false;
+            }
+        }
+        true
+    }
+}
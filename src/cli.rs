@@ -0,0 +1,108 @@
+//! Subcommand-based CLI surface. Each subcommand owns a typed args struct
+//! with its own validation and `--help`, so new modes don't keep
+//! overloading trailing positional arguments.
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::hasher::HashType;
+use crate::output::OutputFormat;
+
+#[derive(Parser)]
+#[command(name = "nsrl-filter", about = "Filter file lists against an NSRL RDS database", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Split a pre-hashed RDS CSV into known/unknown software
+    Filter(FilterArgs),
+    /// Hash files from disk and split them into known/unknown software
+    Hash(HashArgs),
+    /// Merge an NSRL RDS delta release into the database
+    Update(UpdateArgs),
+    /// Print summary statistics about the database
+    Stats(StatsArgs),
+}
+
+#[derive(clap::Args)]
+pub struct FilterArgs {
+    /// Path to the NSRL RDS SQLite database
+    pub database: PathBuf,
+    /// Path to the RDS-format file list CSV
+    pub filelist: PathBuf,
+    /// Only process files with one of these extensions
+    pub extensions: Vec<String>,
+
+    /// Skip the Bloom filter pre-screen and query SQLite directly
+    #[arg(long)]
+    pub no_bloom: bool,
+    /// Number of read-only connections to spread lookups across (1 = single-threaded)
+    #[arg(long, default_value_t = default_threads())]
+    pub threads: usize,
+    /// Output format for known_software/unknown_software
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    pub format: OutputFormat,
+    /// Write a Prometheus metrics snapshot to this path periodically
+    #[arg(long)]
+    pub metrics_file: Option<String>,
+    /// Serve Prometheus metrics over HTTP at host:port
+    #[arg(long)]
+    pub metrics_addr: Option<String>,
+
+    /// Number of records per processing batch
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(usize).range(1..))]
+    pub batch_size: usize,
+    /// Number of batches between transaction commits
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(usize).range(1..))]
+    pub commit_interval: usize,
+    /// Number of records between progress bar updates
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    pub progress_interval: u64,
+
+    /// Emit a uniform reservoir sample of N unknown records for manual triage
+    #[arg(long)]
+    pub sample: Option<usize>,
+    /// Seed for the --sample reservoir sampler (same seed + same input = same sample)
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+#[derive(clap::Args)]
+pub struct HashArgs {
+    /// Path to the NSRL RDS SQLite database
+    pub database: PathBuf,
+    /// Directory to walk, or a CSV/newline file of paths to hash
+    pub scan_target: PathBuf,
+
+    /// Hash algorithm to compute for each file
+    #[arg(long, value_enum, default_value_t = HashType::Sha1)]
+    pub algo: HashType,
+    /// Output format for known_software/unknown_software
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    pub format: OutputFormat,
+}
+
+#[derive(clap::Args)]
+pub struct UpdateArgs {
+    /// Path to the NSRL RDS SQLite database to update in place
+    pub database: PathBuf,
+    /// Delta SQLite database, or a CSV of new hashes, to merge in
+    pub delta: PathBuf,
+
+    /// Record this merge as a daily update rather than a full one
+    #[arg(long)]
+    pub daily: bool,
+}
+
+#[derive(clap::Args)]
+pub struct StatsArgs {
+    /// Path to the NSRL RDS SQLite database
+    pub database: PathBuf,
+}
+
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
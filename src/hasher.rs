@@ -0,0 +1,104 @@
+//! On-disk hashing for the `hash` subcommand: compute digests for a directory
+//! (or a CSV/newline list of paths) instead of requiring a pre-hashed RDS
+//! CSV with sha1/md5 columns already populated.
+
+use md5::Context as Md5Context;
+use sha1::{Digest, Sha1};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+// Read files in fixed-size chunks so hashing never loads a full file into
+// memory, no matter how large it is.
+const HASH_CHUNK_SIZE: usize = 1024 * 1024; // 1MB
+
+// Limited to the columns `determine_table_and_query` actually knows how to
+// match against (sha1/md5). Add a variant here only once the schema
+// detection and queries also understand the corresponding column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HashType {
+    Md5,
+    Sha1,
+}
+
+impl fmt::Display for HashType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HashType::Md5 => "md5",
+            HashType::Sha1 => "sha1",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Stream `path` through a `BufReader` in fixed-size chunks and return its
+/// hex digest under the requested algorithm.
+pub fn compute_file_hash(path: &Path, hash_type: HashType) -> io::Result<String> {
+    let file = File::open(path)?;
+    let mut reader = BufReader::new(file);
+    let mut buffer = vec![0u8; HASH_CHUNK_SIZE];
+
+    match hash_type {
+        HashType::Md5 => {
+            let mut ctx = Md5Context::new();
+            loop {
+                let bytes_read = reader.read(&mut buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                ctx.consume(&buffer[..bytes_read]);
+            }
+            Ok(format!("{:x}", ctx.compute()))
+        }
+        HashType::Sha1 => {
+            let mut hasher = Sha1::new();
+            loop {
+                let bytes_read = reader.read(&mut buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..bytes_read]);
+            }
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+    }
+}
+
+/// Resolve the `hash` subcommand's scan target into a flat list of file paths: directories
+/// are walked recursively, while a plain file is treated as a CSV/newline
+/// list of paths (one per line, optionally comma-separated).
+pub fn collect_file_paths(input: &Path) -> io::Result<Vec<PathBuf>> {
+    if input.is_dir() {
+        let mut paths = Vec::new();
+        collect_dir_recursive(input, &mut paths)?;
+        Ok(paths)
+    } else {
+        let contents = fs::read_to_string(input)?;
+        let paths = contents
+            .lines()
+            .filter_map(|line| {
+                let trimmed = line.split(',').next().unwrap_or("").trim();
+                if trimmed.is_empty() {
+                    None
+                } else {
+                    Some(PathBuf::from(trimmed))
+                }
+            })
+            .collect();
+        Ok(paths)
+    }
+}
+
+fn collect_dir_recursive(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_dir_recursive(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
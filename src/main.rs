@@ -1,19 +1,40 @@
 use rusqlite::{params, Connection, Result as SqlResult};
-use std::env;
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufReader};
-use csv::{Reader, ReaderBuilder, Writer, WriterBuilder};
+use csv::{Reader, ReaderBuilder, StringRecord};
 use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
 use std::time::{Duration, Instant};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-// Increase batch size for better performance
-const BATCH_SIZE: usize = 10000;
-// How often to update the progress bar (in records)
-const PROGRESS_UPDATE_INTERVAL: u64 = 10000;
-// How often to commit transactions (in batches)
-const COMMIT_INTERVAL: usize = 5;
+use clap::Parser;
+
+mod bloom;
+use bloom::BloomFilter;
+
+mod hasher;
+use hasher::{collect_file_paths, compute_file_hash};
+
+mod parallel;
+use parallel::ConnectionPool;
+
+mod output;
+use output::RecordWriter;
+
+mod metrics;
+use metrics::Metrics;
+
+mod update;
+use update::{ensure_updates_table, merge_csv_delta, merge_sqlite_delta, record_update};
+
+mod sample;
+use sample::ReservoirSampler;
+
+mod cli;
+use cli::{Cli, Command, FilterArgs, HashArgs, StatsArgs, UpdateArgs};
+
+// Target false-positive rate for the Bloom filter pre-screen.
+const BLOOM_TARGET_FP_RATE: f64 = 1e-6;
 
 fn determine_table_and_query(conn: &Connection) -> SqlResult<(String, String)> {
     // Check if METADATA table exists (preferred)
@@ -24,7 +45,7 @@ fn determine_table_and_query(conn: &Connection) -> SqlResult<(String, String)> {
     )?;
 
     if metadata_exists {
-        return Ok(("METADATA".to_string(), 
+        return Ok(("METADATA".to_string(),
             "SELECT EXISTS(SELECT 1 FROM METADATA WHERE sha1 = ? OR md5 = ?)".to_string()));
     }
 
@@ -36,7 +57,7 @@ fn determine_table_and_query(conn: &Connection) -> SqlResult<(String, String)> {
     )?;
 
     if file_exists {
-        return Ok(("FILE".to_string(), 
+        return Ok(("FILE".to_string(),
             "SELECT EXISTS(SELECT 1 FROM FILE WHERE sha1 = ? OR md5 = ?)".to_string()));
     }
 
@@ -51,13 +72,13 @@ fn ensure_indexes(conn: &Connection, table_name: &str) -> SqlResult<()> {
         params![format!("{}_sha1_idx", table_name)],
         |row| row.get(0)
     )?;
-    
+
     let md5_index_exists: bool = conn.query_row(
         "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type='index' AND name=?)",
         params![format!("{}_md5_idx", table_name)],
         |row| row.get(0)
     )?;
-    
+
     // Create indexes if they don't exist
     if !sha1_index_exists {
         println!("Creating index on sha1 column...");
@@ -66,7 +87,7 @@ fn ensure_indexes(conn: &Connection, table_name: &str) -> SqlResult<()> {
             []
         )?;
     }
-    
+
     if !md5_index_exists {
         println!("Creating index on md5 column...");
         conn.execute(
@@ -74,31 +95,64 @@ fn ensure_indexes(conn: &Connection, table_name: &str) -> SqlResult<()> {
             []
         )?;
     }
-    
+
     Ok(())
 }
 
+// Load every sha1/md5 value from the METADATA/FILE table into a Bloom filter
+// so `process_batch` can skip SQLite entirely for hashes that are definitely
+// unknown. Sized from the number of non-empty sha1/md5 values (up to two
+// per row) at BLOOM_TARGET_FP_RATE.
+fn build_bloom_filter(conn: &Connection, table_name: &str) -> SqlResult<BloomFilter> {
+    let item_count: i64 = conn.query_row(
+        &format!("SELECT COUNT(NULLIF(sha1, '')) + COUNT(NULLIF(md5, '')) FROM {}", table_name),
+        [],
+        |row| row.get(0),
+    )?;
+
+    let mut filter = BloomFilter::new(item_count.max(0) as u64, BLOOM_TARGET_FP_RATE);
+
+    let mut stmt = conn.prepare(&format!("SELECT sha1, md5 FROM {}", table_name))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let sha1: Option<String> = row.get(0)?;
+        let md5: Option<String> = row.get(1)?;
+        if let Some(sha1) = sha1.filter(|s| !s.is_empty()) {
+            filter.insert_hash(&sha1);
+        }
+        if let Some(md5) = md5.filter(|s| !s.is_empty()) {
+            filter.insert_hash(&md5);
+        }
+    }
+
+    Ok(filter)
+}
+
 // Helper function to process a batch of records
 fn process_batch(
     batch: &mut Vec<csv::StringRecord>,
     stmt: &mut rusqlite::Statement,
-    known_writer: &mut Writer<File>,
-    unknown_writer: &mut Writer<File>,
+    bloom_filter: Option<&BloomFilter>,
+    known_writer: &mut RecordWriter,
+    unknown_writer: &mut RecordWriter,
     known_count: &mut u64,
     unknown_count: &mut u64,
     empty_hash_count: &mut u64,
     error_count: &mut u64,
     processed_hashes: &mut HashSet<String>,
-
+    mut sampler: Option<&mut ReservoirSampler>,
 ) -> Result<(), Box<dyn Error>> {
     for record in batch.iter() {
         // Extension filtering is now done before adding to batch, so we don't need to check here
-        
+
         let md5 = record.get(6).unwrap_or("").trim();
         let sha1 = record.get(7).unwrap_or("").trim();
-        
+
         if md5.is_empty() && sha1.is_empty() {
             unknown_writer.write_record(record.iter())?;
+            if let Some(sampler) = sampler.as_deref_mut() {
+                sampler.offer(record);
+            }
             *unknown_count += 1;
             *empty_hash_count += 1;
             continue;
@@ -106,27 +160,34 @@ fn process_batch(
 
         // Create a hash key using SHA-1 (preferred) or MD5
         let hash_key = if !sha1.is_empty() { sha1.to_string() } else { md5.to_string() };
-        
+
         // Skip if we've already processed this hash
-        if !processed_hashes.insert(hash_key) {
+        if !processed_hashes.insert(hash_key.clone()) {
             continue;
         }
 
-        let is_known: bool = match stmt.query_row(
-            params![
-                if !sha1.is_empty() { sha1 } else { md5 },
-                if !md5.is_empty() { md5 } else { sha1 }
-            ],
-            |row| row.get::<_, bool>(0)
-        ) {
-            Ok(result) => result,
-            Err(e) => {
-                *error_count += 1;
-                if *error_count <= 5 {
-                    // Only print the first few errors to avoid flooding the console
-                    eprintln!("Query error: {} (sha1={}, md5={})", e, sha1, md5);
+        // A Bloom filter miss means the hash is definitely not in the
+        // database, so we can skip the SQLite round trip entirely. A hit
+        // still needs confirming, since the filter can false-positive.
+        let is_known: bool = if bloom_filter.is_some_and(|bf| !bf.contains_hash(&hash_key)) {
+            false
+        } else {
+            match stmt.query_row(
+                params![
+                    if !sha1.is_empty() { sha1 } else { md5 },
+                    if !md5.is_empty() { md5 } else { sha1 }
+                ],
+                |row| row.get::<_, bool>(0)
+            ) {
+                Ok(result) => result,
+                Err(e) => {
+                    *error_count += 1;
+                    if *error_count <= 5 {
+                        // Only print the first few errors to avoid flooding the console
+                        eprintln!("Query error: {} (sha1={}, md5={})", e, sha1, md5);
+                    }
+                    false
                 }
-                false
             }
         };
 
@@ -135,41 +196,313 @@ fn process_batch(
             *known_count += 1;
         } else {
             unknown_writer.write_record(record.iter())?;
+            if let Some(sampler) = sampler.as_deref_mut() {
+                sampler.offer(record);
+            }
             *unknown_count += 1;
         }
     }
-    
+
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let start_time = Instant::now();
-    
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 3 {
-        eprintln!("Usage: {} <database.sqlite> <filelist.csv> [extensions...]", args[0]);
-        eprintln!("Example: {} db.sqlite files.csv exe dll sys", args[0]);
-        std::process::exit(1);
+// Worker-pool counterpart to `process_batch`: instead of querying one hash
+// at a time against a single connection, collect every not-yet-seen hash
+// in the batch first, dispatch the whole set across the connection pool's
+// rayon workers in one call, then write results back out in the batch's
+// original order. `processed_hashes` is still owned by the single writer
+// thread, so dedup stays exactly as it was in the single-threaded path.
+fn process_batch_pooled(
+    batch: &mut Vec<csv::StringRecord>,
+    pool: &ConnectionPool,
+    query: &str,
+    bloom_filter: Option<&BloomFilter>,
+    known_writer: &mut RecordWriter,
+    unknown_writer: &mut RecordWriter,
+    known_count: &mut u64,
+    unknown_count: &mut u64,
+    empty_hash_count: &mut u64,
+    error_count: &mut u64,
+    processed_hashes: &mut HashSet<String>,
+    mut sampler: Option<&mut ReservoirSampler>,
+) -> Result<(), Box<dyn Error>> {
+    // First pass: gather the lookups this batch actually needs. A hash
+    // that's a Bloom filter miss, or that we've already resolved in an
+    // earlier batch, doesn't need a DB round trip at all.
+    let mut lookup_params: Vec<(String, String)> = Vec::new();
+    let mut lookup_index: HashMap<String, Option<usize>> = HashMap::new();
+
+    for record in batch.iter() {
+        let md5 = record.get(6).unwrap_or("").trim();
+        let sha1 = record.get(7).unwrap_or("").trim();
+
+        if md5.is_empty() && sha1.is_empty() {
+            continue;
+        }
+
+        let hash_key = if !sha1.is_empty() { sha1.to_string() } else { md5.to_string() };
+        if processed_hashes.contains(&hash_key) || lookup_index.contains_key(&hash_key) {
+            continue;
+        }
+
+        if bloom_filter.is_some_and(|bf| !bf.contains_hash(&hash_key)) {
+            lookup_index.insert(hash_key, None);
+            continue;
+        }
+
+        lookup_index.insert(hash_key, Some(lookup_params.len()));
+        lookup_params.push((
+            if !sha1.is_empty() { sha1.to_string() } else { md5.to_string() },
+            if !md5.is_empty() { md5.to_string() } else { sha1.to_string() },
+        ));
     }
-    let db_path = &args[1];
-    let csv_path = &args[2];
-    
+
+    let (results, lookup_errors) = pool.lookup_known(query, &lookup_params);
+    *error_count += lookup_errors;
+
+    // Second pass: write every record in its original order, using the
+    // batched results (or the Bloom-miss/dedup shortcuts above).
+    for record in batch.iter() {
+        let md5 = record.get(6).unwrap_or("").trim();
+        let sha1 = record.get(7).unwrap_or("").trim();
+
+        if md5.is_empty() && sha1.is_empty() {
+            unknown_writer.write_record(record.iter())?;
+            if let Some(sampler) = sampler.as_deref_mut() {
+                sampler.offer(record);
+            }
+            *unknown_count += 1;
+            *empty_hash_count += 1;
+            continue;
+        }
+
+        let hash_key = if !sha1.is_empty() { sha1.to_string() } else { md5.to_string() };
+        if !processed_hashes.insert(hash_key.clone()) {
+            continue;
+        }
+
+        let is_known = match lookup_index.get(&hash_key) {
+            Some(Some(idx)) => results[*idx],
+            _ => false,
+        };
+
+        if is_known {
+            known_writer.write_record(record.iter())?;
+            *known_count += 1;
+        } else {
+            unknown_writer.write_record(record.iter())?;
+            if let Some(sampler) = sampler.as_deref_mut() {
+                sampler.offer(record);
+            }
+            *unknown_count += 1;
+        }
+    }
+
+    Ok(())
+}
+
+// Triage a directory (or a CSV/newline list of file paths) against the
+// NSRL database by hashing each file in-flight, instead of requiring a
+// pre-hashed RDS CSV with sha1/md5 columns already populated.
+fn run_hash(args: HashArgs) -> Result<(), Box<dyn Error>> {
+    println!("Opening database: {}", args.database.display());
+    let conn = Connection::open(&args.database)?;
+
+    println!("Applying SQLite performance optimizations...");
+    conn.execute_batch("
+        PRAGMA synchronous = OFF;
+        PRAGMA journal_mode = MEMORY;
+        PRAGMA cache_size = -2000000;
+        PRAGMA temp_store = MEMORY;
+        PRAGMA mmap_size = 30000000000;
+    ")?;
+
+    let (table_name, query) = determine_table_and_query(&conn)
+        .map_err(|_| "Error: Database must contain either a METADATA table or FILE view with sha1 column")?;
+    println!("Using table/view: {}", table_name);
+
+    match ensure_indexes(&conn, &table_name) {
+        Ok(_) => println!("Indexes verified."),
+        Err(e) => println!("Warning: Could not create indexes: {}", e),
+    }
+
+    println!("Loading known hashes into Bloom filter pre-screen...");
+    let bloom_filter = build_bloom_filter(&conn, &table_name)?;
+
+    println!("Collecting files under {}...", args.scan_target.display());
+    let paths = collect_file_paths(&args.scan_target)?;
+    println!("Hashing {} files with {}...", paths.len(), args.algo);
+
+    let mut stmt = conn.prepare(&query)?;
+
+    let scan_headers = StringRecord::from(vec!["hash", "path", "size"]);
+    let mut known_writer = RecordWriter::new("known_software", args.format, &scan_headers)?;
+    let mut unknown_writer = RecordWriter::new("unknown_software", args.format, &scan_headers)?;
+
+    let pb = ProgressBar::new(paths.len() as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg} - ETA: {eta_precise}")?
+        .progress_chars("##-"));
+    pb.set_message("Hashing files...");
+
+    let mut known_count = 0u64;
+    let mut unknown_count = 0u64;
+    let mut error_count = 0u64;
+
+    for path in &paths {
+        let size = match fs::metadata(path) {
+            Ok(meta) => meta.len(),
+            Err(e) => {
+                error_count += 1;
+                eprintln!("Could not stat {}: {}", path.display(), e);
+                pb.inc(1);
+                continue;
+            }
+        };
+
+        let hash = match compute_file_hash(path, args.algo) {
+            Ok(hash) => hash,
+            Err(e) => {
+                error_count += 1;
+                eprintln!("Could not hash {}: {}", path.display(), e);
+                pb.inc(1);
+                continue;
+            }
+        };
+
+        let (sha1_param, md5_param) = match args.algo {
+            hasher::HashType::Sha1 => (hash.as_str(), ""),
+            hasher::HashType::Md5 => ("", hash.as_str()),
+        };
+
+        let is_known = if !bloom_filter.contains_hash(&hash) {
+            false
+        } else {
+            stmt.query_row(params![sha1_param, md5_param], |row| row.get::<_, bool>(0))
+                .unwrap_or(false)
+        };
+
+        let path_str = path.display().to_string();
+        if is_known {
+            known_writer.write_record([hash.as_str(), path_str.as_str(), &size.to_string()])?;
+            known_count += 1;
+        } else {
+            unknown_writer.write_record([hash.as_str(), path_str.as_str(), &size.to_string()])?;
+            unknown_count += 1;
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish_with_message("Hashing complete!");
+    known_writer.flush()?;
+    unknown_writer.flush()?;
+
+    println!("\nSummary:");
+    println!("  Known software: {}", known_count);
+    println!("  Unknown software: {}", unknown_count);
+    if error_count > 0 {
+        println!("  Errors: {}", error_count);
+    }
+
+    Ok(())
+}
+
+// Merge an NSRL RDS delta release (a delta SQLite database, or a CSV of
+// new hashes) into the main database without rebuilding it from scratch.
+fn run_update(args: UpdateArgs) -> Result<(), Box<dyn Error>> {
+    println!("Opening database: {}", args.database.display());
+    let mut conn = Connection::open(&args.database)?;
+
+    let (table_name, _query) = determine_table_and_query(&conn)
+        .map_err(|_| "Error: Database must contain either a METADATA table or FILE view with sha1 column")?;
+    if table_name != "METADATA" {
+        return Err("Error: delta updates require a METADATA table to merge into (found only a FILE view)".into());
+    }
+
+    ensure_updates_table(&conn)?;
+
+    println!("Merging delta from {}...", args.delta.display());
+    let merged = match args.delta.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("csv") => {
+            merge_csv_delta(&mut conn, &table_name, &args.delta, 10_000)?
+        }
+        _ => merge_sqlite_delta(&conn, &table_name, &args.delta)?,
+    };
+
+    println!("Refreshing indexes...");
+    ensure_indexes(&conn, &table_name)?;
+
+    record_update(&conn, args.daily)?;
+
+    println!(
+        "Merged {} new row(s) ({} update).",
+        merged,
+        if args.daily { "daily" } else { "full" }
+    );
+
+    Ok(())
+}
+
+// Print summary statistics about the database: which table backs it, how
+// many rows it holds, and (if any) the most recent delta updates applied.
+fn run_stats(args: StatsArgs) -> Result<(), Box<dyn Error>> {
+    println!("Opening database: {}", args.database.display());
+    let conn = Connection::open(&args.database)?;
+
+    let (table_name, _query) = determine_table_and_query(&conn)
+        .map_err(|_| "Error: Database must contain either a METADATA table or FILE view with sha1 column")?;
+    let row_count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", table_name), [], |row| row.get(0))?;
+
+    println!("Table/view: {}", table_name);
+    println!("Rows: {}", row_count);
+
+    let updates_table_exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type='table' AND name='updates')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    if updates_table_exists {
+        let mut stmt = conn.prepare("SELECT id, is_daily, applied_at FROM updates ORDER BY id DESC LIMIT 10")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, bool>(1)?, row.get::<_, String>(2)?))
+        })?;
+
+        println!("Recent delta updates:");
+        for row in rows {
+            let (id, is_daily, applied_at) = row?;
+            println!("  #{} {} applied at {}", id, if is_daily { "daily" } else { "full" }, applied_at);
+        }
+    } else {
+        println!("No delta updates have been applied yet.");
+    }
+
+    Ok(())
+}
+
+fn run_filter(args: FilterArgs) -> Result<(), Box<dyn Error>> {
+    let start_time = Instant::now();
+
+    let db_path = &args.database;
+    let csv_path = &args.filelist;
+
     // Process optional extensions
-    let extensions = if args.len() > 3 {
-        let exts: Vec<String> = args[3..].iter()
+    let extensions = if args.extensions.is_empty() {
+        None
+    } else {
+        let exts: Vec<String> = args.extensions.iter()
             .map(|ext| ext.trim_start_matches('.')
                 .to_lowercase()
                 .to_string())
             .collect();
         println!("Filtering for extensions: {}", exts.join(", "));
         Some(exts)
-    } else {
-        None
     };
 
-    println!("Opening database: {}", db_path);
+    println!("Opening database: {}", db_path.display());
     let mut conn = Connection::open(db_path)?;
-    
+
     // Enable performance optimizations
     println!("Applying SQLite performance optimizations...");
     conn.execute_batch("
@@ -184,14 +517,44 @@ fn main() -> Result<(), Box<dyn Error>> {
     let (table_name, query) = determine_table_and_query(&conn)
         .map_err(|_| "Error: Database must contain either a METADATA table or FILE view with sha1 column")?;
     println!("Using table/view: {}", table_name);
-    
+
     // Ensure indexes exist for better query performance
     match ensure_indexes(&conn, &table_name) {
         Ok(_) => println!("Indexes verified."),
         Err(e) => println!("Warning: Could not create indexes: {}", e),
     }
 
-    println!("Opening CSV file: {}", csv_path);
+    // Build the Bloom filter pre-screen unless the caller opted out. Most
+    // file lists are overwhelmingly unknown, so this turns the common case
+    // into a memory-only operation instead of a SQLite round trip.
+    let bloom_filter = if args.no_bloom {
+        println!("Bloom filter pre-screen disabled (--no-bloom).");
+        None
+    } else {
+        println!("Loading known hashes into Bloom filter pre-screen...");
+        let filter = build_bloom_filter(&conn, &table_name)?;
+        println!("Bloom filter ready.");
+        Some(filter)
+    };
+
+    // Above one thread, skip the single shared connection/transaction and
+    // dispatch lookups across a pool of read-only connections instead.
+    let pool = if args.threads > 1 {
+        println!("Opening {} read-only connections for parallel lookups...", args.threads);
+        Some(ConnectionPool::open(db_path, args.threads)?)
+    } else {
+        None
+    };
+
+    // --metrics-file takes a periodic snapshot; --metrics-addr serves the
+    // same snapshot live over HTTP. Either, both, or neither may be set.
+    let metrics = Metrics::new();
+    if let Some(addr) = &args.metrics_addr {
+        println!("Serving Prometheus metrics on http://{}/metrics", addr);
+        metrics.serve(addr)?;
+    }
+
+    println!("Opening CSV file: {}", csv_path.display());
     let mut rdr = Reader::from_path(csv_path)?;
     let headers = rdr.headers()?.clone();
 
@@ -205,21 +568,21 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Create a HashSet to track unique hashes
     let mut processed_hashes = HashSet::new();
-    
+
     // Pre-scan to count unique hashes
     let mut pre_scan_rdr = ReaderBuilder::new()
         .buffer_capacity(1024 * 1024) // 1MB buffer for reading
         .from_path(csv_path)?;
-    
+
     let pre_scan_pb = ProgressBar::new(total_records);
     pre_scan_pb.set_style(ProgressStyle::default_bar()
-        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")?  
+        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")?
         .progress_chars("##-"));
     pre_scan_pb.set_message("Scanning for unique hashes...");
-    
+
     // Skip the header
     pre_scan_rdr.headers()?;
-    
+
     // Count unique hashes
     let mut scanned_count = 0;
     for result in pre_scan_rdr.records() {
@@ -227,13 +590,13 @@ fn main() -> Result<(), Box<dyn Error>> {
             Ok(record) => {
                 let md5 = record.get(6).unwrap_or("").trim();
                 let sha1 = record.get(7).unwrap_or("").trim();
-                
+
                 if !md5.is_empty() || !sha1.is_empty() {
                     // Create a hash key using SHA-1 (preferred) or MD5
                     let hash_key = if !sha1.is_empty() { sha1.to_string() } else { md5.to_string() };
                     processed_hashes.insert(hash_key);
                 }
-                
+
                 scanned_count += 1;
                 if scanned_count % 10000 == 0 {
                     pre_scan_pb.set_position(scanned_count);
@@ -244,44 +607,44 @@ fn main() -> Result<(), Box<dyn Error>> {
             }
         }
     }
-    
-    pre_scan_pb.finish_with_message(format!("Found {} unique hashes in {} total records", 
+
+    pre_scan_pb.finish_with_message(format!("Found {} unique hashes in {} total records",
         processed_hashes.len(), total_records));
-    
+
     // Count how many unique hashes match the extension filter
     let mut extension_filtered_hashes = HashSet::new();
     let extension_filter_pb = ProgressBar::new(processed_hashes.len() as u64);
-    
+
     if let Some(exts) = &extensions {
         println!("Filtering unique hashes by extension...");
         extension_filter_pb.set_style(ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")?  
+            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")?
             .progress_chars("##-"));
         extension_filter_pb.set_message("Filtering by extension...");
-        
+
         // Reopen the CSV file to scan for extension matches
         let mut ext_scan_rdr = ReaderBuilder::new()
             .buffer_capacity(1024 * 1024) // 1MB buffer for reading
             .from_path(csv_path)?;
-        
+
         // Skip the header
         ext_scan_rdr.headers()?;
-        
+
         let mut ext_scanned_count = 0;
         for result in ext_scan_rdr.records() {
             match result {
                 Ok(record) => {
                     let md5 = record.get(6).unwrap_or("").trim();
                     let sha1 = record.get(7).unwrap_or("").trim();
-                    
+
                     // Skip records with empty hashes
                     if md5.is_empty() && sha1.is_empty() {
                         continue;
                     }
-                    
+
                     // Create a hash key using SHA-1 (preferred) or MD5
                     let hash_key = if !sha1.is_empty() { sha1.to_string() } else { md5.to_string() };
-                    
+
                     // Only process if this is a unique hash we haven't filtered yet
                     if processed_hashes.contains(&hash_key) && !extension_filtered_hashes.contains(&hash_key) {
                         // Get the extension directly from the Extension column
@@ -291,7 +654,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                             // Fallback to index 2 if Extension column not found
                             record.get(2).unwrap_or("").trim().to_lowercase()
                         };
-                        
+
                         // Check if extension matches
                         let normalized_ext = file_ext.trim_start_matches('.');
                         if exts.iter().any(|ext| {
@@ -301,7 +664,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                             extension_filtered_hashes.insert(hash_key);
                         }
                     }
-                    
+
                     ext_scanned_count += 1;
                     if ext_scanned_count % 10000 == 0 {
                         extension_filter_pb.set_position(extension_filtered_hashes.len() as u64);
@@ -312,42 +675,41 @@ fn main() -> Result<(), Box<dyn Error>> {
                 }
             }
         }
-        
-        extension_filter_pb.finish_with_message(format!("Found {} hashes matching extension filter", 
+
+        extension_filter_pb.finish_with_message(format!("Found {} hashes matching extension filter",
             extension_filtered_hashes.len()));
-        
+
         // Replace the processed_hashes with only those that match the extension filter
         processed_hashes = extension_filtered_hashes;
     }
-    
+
     // Clear the HashSet to reuse it during actual processing
     let unique_hash_count = processed_hashes.len() as u64;
     processed_hashes.clear();
 
     // Create multi-progress display for better visualization
     let mp = MultiProgress::new();
-    
+
     // Main progress bar for overall progress (now based on filtered unique hashes)
     let pb = mp.add(ProgressBar::new(unique_hash_count));
     pb.set_style(ProgressStyle::default_bar()
-        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg} - ETA: {eta_precise}")?  
+        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg} - ETA: {eta_precise}")?
         .progress_chars("##-"));
     pb.set_message("Processing filtered hashes...");
-    
+
     // Status bar for statistics
     let status_bar = mp.add(ProgressBar::new(100));
     status_bar.set_style(ProgressStyle::default_bar()
-        .template("Known: {prefix} | Unknown: {msg} | Unique: {pos}/{len} | Total: {per_sec}")?); 
-
-    // Configure CSV writers with performance options
-    let mut known_writer = WriterBuilder::new()
-        .buffer_capacity(65536) // 64KB buffer
-        .from_path("known_software.csv")?;
-    let mut unknown_writer = WriterBuilder::new()
-        .buffer_capacity(65536) // 64KB buffer
-        .from_path("unknown_software.csv")?;
-    known_writer.write_record(&headers)?;
-    unknown_writer.write_record(&headers)?;
+        .template("Known: {prefix} | Unknown: {msg} | Unique: {pos}/{len} | Total: {per_sec}")?);
+
+    // Configure the known/unknown writers in the requested output format
+    let mut known_writer = RecordWriter::new("known_software", args.format, &headers)?;
+    let mut unknown_writer = RecordWriter::new("unknown_software", args.format, &headers)?;
+
+    // --sample draws a deterministic reservoir sample of the unknown
+    // stream alongside the full unknown_software output, for analysts who
+    // want a repeatable subset to triage by hand.
+    let mut sampler = args.sample.map(|n| ReservoirSampler::new(n, args.seed));
 
     let mut known_count = 0;
     let mut unknown_count = 0;
@@ -357,22 +719,22 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut last_update_count = 0;
     let mut records_processed = 0;
     let mut unique_processed = 0;
-    
+
     // Reopen the CSV file for streaming processing
     let mut rdr = ReaderBuilder::new()
         .buffer_capacity(1024 * 1024) // 1MB buffer for reading
         .from_path(csv_path)?;
-    
+
     // Skip the header
     rdr.headers()?;
-    
+
     println!("Starting batch processing...");
-    
+
     // Process in batches with periodic transaction commits
-    let mut batch_records = Vec::with_capacity(BATCH_SIZE);
+    let mut batch_records = Vec::with_capacity(args.batch_size);
     let mut tx = conn.transaction()?;
     let mut stmt = tx.prepare(&query)?;
-    
+
     // Process records in a streaming fashion
     for result in rdr.records() {
         match result {
@@ -386,7 +748,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                         // Fallback to index 2 if Extension column not found
                         record.get(2).unwrap_or("").trim().to_lowercase()
                     };
-                    
+
                     // Skip if extension doesn't match
                     let normalized_ext = file_ext.trim_start_matches('.');
                     if !exts.iter().any(|ext| {
@@ -396,65 +758,100 @@ fn main() -> Result<(), Box<dyn Error>> {
                         continue;
                     }
                 }
-                
+
                 batch_records.push(record);
-                
+
                 // Process a batch when it reaches the batch size
-                if batch_records.len() >= BATCH_SIZE {
+                if batch_records.len() >= args.batch_size {
                     let before_unique = processed_hashes.len();
-                    
-                    process_batch(
-                        &mut batch_records,
-                        &mut stmt,
-                        &mut known_writer,
-                        &mut unknown_writer,
-                        &mut known_count,
-                        &mut unknown_count,
-                        &mut empty_hash_count,
-                        &mut error_count,
+                    let batch_rows = batch_records.len();
+                    let batch_start = Instant::now();
+
+                    if let Some(pool) = &pool {
+                        process_batch_pooled(
+                            &mut batch_records,
+                            pool,
+                            &query,
+                            bloom_filter.as_ref(),
+                            &mut known_writer,
+                            &mut unknown_writer,
+                            &mut known_count,
+                            &mut unknown_count,
+                            &mut empty_hash_count,
+                            &mut error_count,
+                            &mut processed_hashes,
+                            sampler.as_mut(),
+                        )?;
+                    } else {
+                        process_batch(
+                            &mut batch_records,
+                            &mut stmt,
+                            bloom_filter.as_ref(),
+                            &mut known_writer,
+                            &mut unknown_writer,
+                            &mut known_count,
+                            &mut unknown_count,
+                            &mut empty_hash_count,
+                            &mut error_count,
 &mut processed_hashes, // Pass the mutable HashSet reference
-                    )?;
-                    
+                            sampler.as_mut(),
+                        )?;
+                    }
+
+                    metrics.record_batch(batch_start.elapsed(), batch_rows);
+
                     let new_unique = processed_hashes.len() - before_unique;
                     unique_processed += new_unique as u64;
                     records_processed += batch_records.len() as u64;
                     batch_records.clear();
-                    
+
                     // Update progress based on unique hashes processed
                     pb.set_position(unique_processed);
-                    
-                    // Commit transaction periodically
-                    if records_processed % (BATCH_SIZE as u64 * COMMIT_INTERVAL as u64) == 0 {
-                        // Commit current transaction and start a new one
-                        drop(stmt);
-                        tx.commit()?;
-                        tx = conn.transaction()?;
-                        stmt = tx.prepare(&query)?;
-                        
+
+                    // Commit transaction periodically. When the pool is active,
+                    // `conn`/`tx`/`stmt` are never used for lookups (those all go
+                    // through the pool's own read-only connections), so there's
+                    // nothing to commit — just flush the writers on the same
+                    // cadence.
+                    if records_processed % (args.batch_size as u64 * args.commit_interval as u64) == 0 {
+                        if pool.is_none() {
+                            // Commit current transaction and start a new one
+                            drop(stmt);
+                            tx.commit()?;
+                            tx = conn.transaction()?;
+                            stmt = tx.prepare(&query)?;
+                        }
+
                         // Flush writers
                         known_writer.flush()?;
                         unknown_writer.flush()?;
                     }
-                    
+
                     // Update status bar with statistics
-                    if records_processed % PROGRESS_UPDATE_INTERVAL == 0 || 
+                    if records_processed % args.progress_interval == 0 ||
                        last_update_time.elapsed() >= Duration::from_secs(1) {
                         let elapsed = last_update_time.elapsed().as_secs_f64();
                         let records_since_last = records_processed - last_update_count;
-                        
+
                         if elapsed >= 0.5 { // Only update if at least half a second has passed
                             let speed = records_since_last as f64 / elapsed;
-                            status_bar.set_message(format!("{} ({:.1}%)", 
+                            status_bar.set_message(format!("{} ({:.1}%)",
                                 unknown_count,
                                 if unique_processed > 0 { (unknown_count as f64 / unique_processed as f64) * 100.0 } else { 0.0 }));
-                            status_bar.set_prefix(format!("{} ({:.1}%)", 
+                            status_bar.set_prefix(format!("{} ({:.1}%)",
                                 known_count,
                                 if unique_processed > 0 { (known_count as f64 / unique_processed as f64) * 100.0 } else { 0.0 }));
                             status_bar.set_position(unique_processed);
                             status_bar.set_length(unique_hash_count);
-                            
+
                             pb.set_message(format!("Processing at {:.0} records/sec", speed));
-                            
+
+                            metrics.set_counts(known_count, unknown_count, empty_hash_count, error_count);
+                            metrics.set_records_per_second(speed);
+                            if let Some(path) = &args.metrics_file {
+                                metrics.write_snapshot(path)?;
+                            }
+
                             last_update_time = Instant::now();
                             last_update_count = records_processed;
                         }
@@ -470,33 +867,57 @@ fn main() -> Result<(), Box<dyn Error>> {
             }
         }
     }
-    
+
     // Process any remaining records in the last batch
     if !batch_records.is_empty() {
         let before_unique = processed_hashes.len();
-        
-        process_batch(
-            &mut batch_records,
-            &mut stmt,
-            &mut known_writer,
-            &mut unknown_writer,
-            &mut known_count,
-            &mut unknown_count,
-            &mut empty_hash_count,
-            &mut error_count,
-            &mut processed_hashes,
 
-        )?;
-        
+        if let Some(pool) = &pool {
+            process_batch_pooled(
+                &mut batch_records,
+                pool,
+                &query,
+                bloom_filter.as_ref(),
+                &mut known_writer,
+                &mut unknown_writer,
+                &mut known_count,
+                &mut unknown_count,
+                &mut empty_hash_count,
+                &mut error_count,
+                &mut processed_hashes,
+                sampler.as_mut(),
+            )?;
+        } else {
+            process_batch(
+                &mut batch_records,
+                &mut stmt,
+                bloom_filter.as_ref(),
+                &mut known_writer,
+                &mut unknown_writer,
+                &mut known_count,
+                &mut unknown_count,
+                &mut empty_hash_count,
+                &mut error_count,
+                &mut processed_hashes,
+                sampler.as_mut(),
+            )?;
+        }
+
         let new_unique = processed_hashes.len() - before_unique;
         unique_processed += new_unique as u64;
         records_processed += batch_records.len() as u64;
         pb.set_position(unique_processed);
     }
-    
+
     // Commit the final transaction
     drop(stmt);
     tx.commit()?;
+
+    metrics.set_counts(known_count, unknown_count, empty_hash_count, error_count);
+    if let Some(path) = &args.metrics_file {
+        metrics.write_snapshot(path)?;
+    }
+
     // Finish progress bars
     pb.finish_with_message("Processing complete!");
     status_bar.finish_and_clear();
@@ -505,21 +926,31 @@ fn main() -> Result<(), Box<dyn Error>> {
     known_writer.flush()?;
     unknown_writer.flush()?;
 
+    if let Some(sampler) = sampler {
+        let sample_records = sampler.into_records();
+        println!("  Writing reservoir sample of {} unknown record(s)...", sample_records.len());
+        let mut sample_writer = RecordWriter::new("unknown_sample", args.format, &headers)?;
+        for record in &sample_records {
+            sample_writer.write_record(record.iter())?;
+        }
+        sample_writer.flush()?;
+    }
+
     let duration = start_time.elapsed();
     let records_per_second = records_processed as f64 / duration.as_secs_f64();
-    
+
     println!("\nDetailed Summary:");
     println!("  Total records processed: {}", records_processed);
-    println!("  Unique hash values: {} ({:.1}%)", 
+    println!("  Unique hash values: {} ({:.1}%)",
         processed_hashes.len(),
         (processed_hashes.len() as f64 / records_processed as f64) * 100.0);
-    println!("  Known software: {} ({:.1}%)", 
-        known_count, 
+    println!("  Known software: {} ({:.1}%)",
+        known_count,
         (known_count as f64 / processed_hashes.len() as f64) * 100.0);
-    println!("  Unknown software: {} ({:.1}%)", 
-        unknown_count, 
+    println!("  Unknown software: {} ({:.1}%)",
+        unknown_count,
         (unknown_count as f64 / processed_hashes.len() as f64) * 100.0);
-    println!("  Records with empty hashes: {} ({:.1}%)", 
+    println!("  Records with empty hashes: {} ({:.1}%)",
         empty_hash_count,
         (empty_hash_count as f64 / records_processed as f64) * 100.0);
     println!("  Duplicate hash values: {} ({:.1}%)",
@@ -532,4 +963,14 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("  Total processing time: {:.2} seconds", duration.as_secs_f64());
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Filter(args) => run_filter(args),
+        Command::Hash(args) => run_hash(args),
+        Command::Update(args) => run_update(args),
+        Command::Stats(args) => run_stats(args),
+    }
+}
@@ -0,0 +1,173 @@
+//! Prometheus-style metrics for long-running scans: a duration histogram
+//! for batch processing time, a size histogram for rows-per-batch, and
+//! gauges for known/unknown/empty/error counts and current throughput.
+//! Exposed either as a periodic `--metrics-file` snapshot or a tiny
+//! `--metrics-addr` HTTP `/metrics` endpoint, both in Prometheus text
+//! exposition format.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    // Upper bound ("le") of each bucket, ascending, not including +Inf.
+    bucket_bounds: Vec<f64>,
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(name: &'static str, help: &'static str, bucket_bounds: Vec<f64>) -> Self {
+        let bucket_counts = vec![0; bucket_bounds.len()];
+        Histogram { name, help, bucket_bounds, bucket_counts, sum: 0.0, count: 0 }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bucket_bounds.iter().zip(self.bucket_counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        out.push_str(&format!("# HELP {} {}\n", self.name, self.help));
+        out.push_str(&format!("# TYPE {} histogram\n", self.name));
+        // `observe` already increments every bucket whose bound is >= the
+        // observed value, so `bucket_counts[i]` is already the cumulative
+        // "le" count — summing again here would double-count.
+        for (bound, count) in self.bucket_bounds.iter().zip(self.bucket_counts.iter()) {
+            out.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", self.name, bound, count));
+        }
+        out.push_str(&format!("{}_bucket{{le=\"+Inf\"}} {}\n", self.name, self.count));
+        out.push_str(&format!("{}_sum {}\n", self.name, self.sum));
+        out.push_str(&format!("{}_count {}\n", self.name, self.count));
+    }
+}
+
+struct Inner {
+    batch_duration_seconds: Histogram,
+    batch_size_rows: Histogram,
+    known_count: u64,
+    unknown_count: u64,
+    empty_count: u64,
+    error_count: u64,
+    records_per_second: f64,
+}
+
+/// Shared handle passed into the processing loop; cheap to clone (`Arc`)
+/// so it can be handed to both the batch loop and the HTTP server thread.
+#[derive(Clone)]
+pub struct Metrics(Arc<Mutex<Inner>>);
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics(Arc::new(Mutex::new(Inner {
+            batch_duration_seconds: Histogram::new(
+                "nsrl_filter_batch_duration_seconds",
+                "Time to process one batch of records",
+                vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+            ),
+            batch_size_rows: Histogram::new(
+                "nsrl_filter_batch_size_rows",
+                "Number of rows processed per batch",
+                vec![100.0, 1000.0, 5000.0, 10000.0, 25000.0, 50000.0],
+            ),
+            known_count: 0,
+            unknown_count: 0,
+            empty_count: 0,
+            error_count: 0,
+            records_per_second: 0.0,
+        })))
+    }
+
+    pub fn record_batch(&self, duration: Duration, rows: usize) {
+        let mut inner = self.0.lock().expect("metrics mutex poisoned");
+        inner.batch_duration_seconds.observe(duration.as_secs_f64());
+        inner.batch_size_rows.observe(rows as f64);
+    }
+
+    pub fn set_counts(&self, known: u64, unknown: u64, empty: u64, errors: u64) {
+        let mut inner = self.0.lock().expect("metrics mutex poisoned");
+        inner.known_count = known;
+        inner.unknown_count = unknown;
+        inner.empty_count = empty;
+        inner.error_count = errors;
+    }
+
+    pub fn set_records_per_second(&self, rate: f64) {
+        let mut inner = self.0.lock().expect("metrics mutex poisoned");
+        inner.records_per_second = rate;
+    }
+
+    /// Render the full snapshot in Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let inner = self.0.lock().expect("metrics mutex poisoned");
+        let mut out = String::new();
+
+        inner.batch_duration_seconds.render(&mut out);
+        inner.batch_size_rows.render(&mut out);
+
+        out.push_str("# HELP nsrl_filter_known_total Known records found so far\n");
+        out.push_str("# TYPE nsrl_filter_known_total gauge\n");
+        out.push_str(&format!("nsrl_filter_known_total {}\n", inner.known_count));
+
+        out.push_str("# HELP nsrl_filter_unknown_total Unknown records found so far\n");
+        out.push_str("# TYPE nsrl_filter_unknown_total gauge\n");
+        out.push_str(&format!("nsrl_filter_unknown_total {}\n", inner.unknown_count));
+
+        out.push_str("# HELP nsrl_filter_empty_hash_total Records with no sha1/md5 so far\n");
+        out.push_str("# TYPE nsrl_filter_empty_hash_total gauge\n");
+        out.push_str(&format!("nsrl_filter_empty_hash_total {}\n", inner.empty_count));
+
+        out.push_str("# HELP nsrl_filter_error_total Query errors encountered so far\n");
+        out.push_str("# TYPE nsrl_filter_error_total gauge\n");
+        out.push_str(&format!("nsrl_filter_error_total {}\n", inner.error_count));
+
+        out.push_str("# HELP nsrl_filter_records_per_second Current processing throughput\n");
+        out.push_str("# TYPE nsrl_filter_records_per_second gauge\n");
+        out.push_str(&format!("nsrl_filter_records_per_second {}\n", inner.records_per_second));
+
+        out
+    }
+
+    /// Write the current snapshot to `path`, overwriting any previous
+    /// contents. Intended to be called periodically from the batch loop.
+    pub fn write_snapshot(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.render())
+    }
+
+    /// Serve `/metrics` over plain HTTP on a background thread until the
+    /// process exits. Intentionally minimal: one blocking accept loop, no
+    /// keep-alive, just enough for a scraper to poll.
+    pub fn serve(&self, addr: &str) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let metrics = self.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let mut discard = [0u8; 1024];
+                let _ = stream.read(&mut discard);
+
+                let body = metrics.render();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        Ok(())
+    }
+}
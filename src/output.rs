@@ -0,0 +1,125 @@
+//! Output writer abstraction so `known_software`/`unknown_software` can be
+//! emitted as CSV (the original format) or as newline-delimited JSON,
+//! without the batch-processing code needing to know which.
+
+use csv::{StringRecord, Writer, WriterBuilder};
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Csv,
+    Jsonl,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Jsonl => "jsonl",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+enum Inner {
+    Csv(Writer<File>),
+    Jsonl(BufWriter<File>),
+}
+
+/// Writes `known_software`/`unknown_software` rows as either CSV or JSONL,
+/// keyed on a shared `base_name` (`known_software.csv`/`known_software.jsonl`).
+pub struct RecordWriter {
+    inner: Inner,
+    headers: StringRecord,
+}
+
+// Header names that should be emitted as JSON numbers rather than strings.
+fn is_numeric_field(header: &str) -> bool {
+    let header = header.to_lowercase();
+    header.contains("size")
+}
+
+// Header names that should be emitted as JSON booleans rather than strings.
+fn is_boolean_field(header: &str) -> bool {
+    let header = header.to_lowercase();
+    header.starts_with("is_") || header.contains("flag") || header == "daily"
+}
+
+// Parse a handful of spellings RDS-style CSVs tend to use for booleans.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "1" | "yes" | "y" => Some(true),
+        "false" | "0" | "no" | "n" => Some(false),
+        _ => None,
+    }
+}
+
+impl RecordWriter {
+    pub fn new(base_name: &str, format: OutputFormat, headers: &StringRecord) -> Result<Self, Box<dyn Error>> {
+        let inner = match format {
+            OutputFormat::Csv => {
+                let mut writer = WriterBuilder::new()
+                    .buffer_capacity(65536)
+                    .from_path(format!("{}.csv", base_name))?;
+                writer.write_record(headers)?;
+                Inner::Csv(writer)
+            }
+            OutputFormat::Jsonl => {
+                let file = File::create(format!("{}.jsonl", base_name))?;
+                Inner::Jsonl(BufWriter::with_capacity(65536, file))
+            }
+        };
+
+        Ok(RecordWriter {
+            inner,
+            headers: headers.clone(),
+        })
+    }
+
+    /// Write one record. Empty fields become JSON `null` rather than `""`;
+    /// fields whose header looks numeric (e.g. a size column) or boolean
+    /// (e.g. an `is_`-prefixed or `*flag` column) are emitted as JSON
+    /// numbers/booleans when they parse cleanly, falling back to a string
+    /// otherwise.
+    pub fn write_record<'a, I: IntoIterator<Item = &'a str>>(&mut self, fields: I) -> Result<(), Box<dyn Error>> {
+        match &mut self.inner {
+            Inner::Csv(writer) => {
+                writer.write_record(fields)?;
+            }
+            Inner::Jsonl(writer) => {
+                let mut object = Map::new();
+                for (header, value) in self.headers.iter().zip(fields) {
+                    let json_value = if value.is_empty() {
+                        Value::Null
+                    } else if is_numeric_field(header) {
+                        match value.parse::<i64>() {
+                            Ok(n) => Value::from(n),
+                            Err(_) => Value::from(value),
+                        }
+                    } else if is_boolean_field(header) {
+                        match parse_bool(value) {
+                            Some(b) => Value::from(b),
+                            None => Value::from(value),
+                        }
+                    } else {
+                        Value::from(value)
+                    };
+                    object.insert(header.to_string(), json_value);
+                }
+                writeln!(writer, "{}", Value::Object(object))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.inner {
+            Inner::Csv(writer) => writer.flush(),
+            Inner::Jsonl(writer) => writer.flush(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Worker-pool mode: spread deduplicated hash lookups across N read-only
+//! SQLite connections via rayon, instead of serializing every lookup
+//! through one connection and prepared statement.
+
+use rayon::prelude::*;
+use rusqlite::{params, Connection, OpenFlags};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// Same tuning as the single-connection path, minus `journal_mode`;
+// read-only connections can share the page cache without risking a write
+// conflict.
+const POOL_PRAGMAS: &str = "
+    PRAGMA synchronous = OFF;
+    PRAGMA cache_size = -2000000;
+    PRAGMA temp_store = MEMORY;
+    PRAGMA mmap_size = 30000000000;
+";
+
+pub struct ConnectionPool {
+    connections: Vec<Mutex<Connection>>,
+}
+
+impl ConnectionPool {
+    /// Open `size` independent read-only connections to `db_path`, each
+    /// tuned with the same PRAGMAs as the main connection.
+    pub fn open(db_path: &Path, size: usize) -> rusqlite::Result<Self> {
+        let size = size.max(1);
+        let mut connections = Vec::with_capacity(size);
+        for _ in 0..size {
+            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            conn.execute_batch(POOL_PRAGMAS)?;
+            connections.push(Mutex::new(conn));
+        }
+        Ok(ConnectionPool { connections })
+    }
+
+    pub fn size(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Look up whether each `(sha1, md5)` pair is known, splitting the
+    /// work evenly across the pool's connections. Each chunk runs on its
+    /// own rayon worker against its own connection and prepared
+    /// statement, so lookups scale close to linearly on a warm page
+    /// cache. Results are returned in the same order as `keys`, alongside
+    /// a count of lookups that errored (and were treated as "unknown"),
+    /// mirroring how the single-connection path tracks `error_count`.
+    pub fn lookup_known(&self, query: &str, keys: &[(String, String)]) -> (Vec<bool>, u64) {
+        if keys.is_empty() {
+            return (Vec::new(), 0);
+        }
+
+        let num_workers = self.size();
+        let chunk_size = keys.len().div_ceil(num_workers).max(1);
+        let error_count = AtomicU64::new(0);
+
+        let results = keys
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let conn = self.connections[i % num_workers]
+                    .lock()
+                    .expect("pooled connection mutex poisoned");
+                let mut stmt = conn
+                    .prepare(query)
+                    .expect("prepare statement on pooled connection");
+                chunk
+                    .iter()
+                    .map(|(sha1, md5)| {
+                        match stmt.query_row(params![sha1, md5], |row| row.get::<_, bool>(0)) {
+                            Ok(result) => result,
+                            Err(e) => {
+                                let seen_so_far = error_count.fetch_add(1, Ordering::Relaxed);
+                                if seen_so_far < 5 {
+                                    // Only print the first few errors to avoid flooding the console
+                                    eprintln!("Query error: {} (sha1={}, md5={})", e, sha1, md5);
+                                }
+                                false
+                            }
+                        }
+                    })
+                    .collect::<Vec<bool>>()
+            })
+            .collect::<Vec<Vec<bool>>>()
+            .into_iter()
+            .flatten()
+            .collect();
+
+        (results, error_count.load(Ordering::Relaxed))
+    }
+}
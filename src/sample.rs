@@ -0,0 +1,70 @@
+//! Deterministic reservoir sampling over the streaming unknown-record
+//! stream, so analysts can pull a repeatable, representative subset for
+//! manual triage without materializing and shuffling the full output.
+
+use csv::StringRecord;
+
+// xorshift64 (13/7/17 shifts) with an xorshift*-style multiplicative
+// output step: small, seedable, and dependency-free, in keeping with
+// the hand-rolled FNV hashing already used for the Bloom filter.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // A zero state never advances under xorshift, so nudge it off zero.
+        Rng(if seed == 0 { 0x9E3779B97F4A7C15 } else { seed })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    /// Uniform-ish value in `0..bound`. Reservoir sampling only needs this
+    /// to decide a replacement slot, so the small modulo bias is fine.
+    fn next_below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+/// Classic reservoir sampling (Algorithm R): keeps the first `capacity`
+/// records unconditionally, then for the i-th record (i >= capacity)
+/// picks a random index in `0..=i` and replaces that slot if it lands
+/// inside the reservoir.
+pub struct ReservoirSampler {
+    capacity: usize,
+    seen: u64,
+    buffer: Vec<StringRecord>,
+    rng: Rng,
+}
+
+impl ReservoirSampler {
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        ReservoirSampler {
+            capacity,
+            seen: 0,
+            buffer: Vec::with_capacity(capacity),
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn offer(&mut self, record: &StringRecord) {
+        if self.buffer.len() < self.capacity {
+            self.buffer.push(record.clone());
+        } else if self.capacity > 0 {
+            let j = self.rng.next_below(self.seen + 1) as usize;
+            if j < self.capacity {
+                self.buffer[j] = record.clone();
+            }
+        }
+        self.seen += 1;
+    }
+
+    pub fn into_records(self) -> Vec<StringRecord> {
+        self.buffer
+    }
+}
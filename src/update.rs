@@ -0,0 +1,168 @@
+//! Delta/incremental NSRL RDS updates: merge a delta SQLite database (or a
+//! CSV of new hashes) into the main `METADATA` table with `INSERT OR
+//! IGNORE`, so a full rebuild isn't needed for every periodic release.
+
+use csv::ReaderBuilder;
+use rusqlite::{params, Connection, Result as SqlResult};
+use std::error::Error;
+use std::path::Path;
+
+/// Create the `updates` tracking table if it doesn't already exist.
+pub fn ensure_updates_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS updates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            is_daily BOOLEAN NOT NULL,
+            applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+}
+
+/// Record that a delta set (full or daily) was applied.
+pub fn record_update(conn: &Connection, is_daily: bool) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO updates (is_daily, applied_at) VALUES (?, CURRENT_TIMESTAMP)",
+        params![is_daily],
+    )?;
+    Ok(())
+}
+
+/// Column names of `table` in the given schema (`"main"` or an attached
+/// database name), in table-definition order.
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {}.table_info({})", schema, table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<SqlResult<Vec<String>>>()?;
+    Ok(columns)
+}
+
+/// Merge every row from a delta SQLite database into `table_name`, wrapped
+/// in one transaction. Only columns `table_name` actually defines *and*
+/// the delta table also has are copied, in `table_name`'s column order, so
+/// `NOT NULL` columns beyond sha1/md5 (FileName, FileSize, ProductCode,
+/// ...) still get populated instead of left null or rejected outright. The
+/// delta is attached as `delta` so the merge is a single `INSERT OR
+/// IGNORE ... SELECT`.
+pub fn merge_sqlite_delta(conn: &Connection, table_name: &str, delta_path: &Path) -> Result<usize, Box<dyn Error>> {
+    let delta_path_str = delta_path.to_string_lossy();
+    conn.execute("ATTACH DATABASE ? AS delta", params![delta_path_str.as_ref()])?;
+
+    let delta_table: String = if conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM delta.sqlite_master WHERE type='table' AND name='METADATA')",
+        [],
+        |row| row.get(0),
+    )? {
+        "METADATA".to_string()
+    } else {
+        "FILE".to_string()
+    };
+
+    let target_columns = table_columns(conn, "main", table_name)?;
+    let delta_columns = table_columns(conn, "delta", &delta_table)?;
+    let shared_columns: Vec<&String> = target_columns
+        .iter()
+        .filter(|c| delta_columns.iter().any(|d| d.eq_ignore_ascii_case(c)))
+        .collect();
+
+    if shared_columns.is_empty() {
+        conn.execute("DETACH DATABASE delta", [])?;
+        return Err(format!(
+            "no shared columns between {} and delta.{}",
+            table_name, delta_table
+        )
+        .into());
+    }
+
+    let column_list = shared_columns
+        .iter()
+        .map(|c| c.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let merged = conn.execute(
+        &format!(
+            "INSERT OR IGNORE INTO {table_name} ({columns}) SELECT {columns} FROM delta.{delta_table}",
+            table_name = table_name,
+            columns = column_list,
+            delta_table = delta_table
+        ),
+        [],
+    )?;
+
+    conn.execute("DETACH DATABASE delta", [])?;
+    Ok(merged)
+}
+
+/// Merge a delta CSV (same RDS file-list layout as the `filter` subcommand)
+/// into `table_name`, batching inserts into transactions of `batch_size`
+/// rows. Only columns present in both the CSV header and `table_name`'s
+/// schema are copied, by name, so `NOT NULL` columns beyond sha1/md5 get
+/// populated whenever the CSV actually carries them.
+pub fn merge_csv_delta(
+    conn: &mut Connection,
+    table_name: &str,
+    csv_path: &Path,
+    batch_size: usize,
+) -> Result<usize, Box<dyn Error>> {
+    let mut reader = ReaderBuilder::new().from_path(csv_path)?;
+    let headers = reader.headers()?.clone();
+
+    let target_columns = table_columns(conn, "main", table_name)?;
+    let shared: Vec<(String, usize)> = target_columns
+        .iter()
+        .filter_map(|column| {
+            headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(column))
+                .map(|idx| (column.clone(), idx))
+        })
+        .collect();
+
+    if shared.is_empty() {
+        return Err(format!("no columns in {} match the delta CSV header", table_name).into());
+    }
+
+    let column_list = shared
+        .iter()
+        .map(|(c, _)| c.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = (1..=shared.len())
+        .map(|i| format!("?{}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let insert_sql = format!(
+        "INSERT OR IGNORE INTO {} ({}) VALUES ({})",
+        table_name, column_list, placeholders
+    );
+
+    let mut merged = 0usize;
+    let mut rows_in_batch = 0usize;
+    let mut tx = conn.transaction()?;
+
+    for result in reader.records() {
+        let record = result?;
+        let md5 = record.get(6).unwrap_or("").trim();
+        let sha1 = record.get(7).unwrap_or("").trim();
+        if md5.is_empty() && sha1.is_empty() {
+            continue;
+        }
+
+        let values: Vec<&str> = shared
+            .iter()
+            .map(|(_, idx)| record.get(*idx).unwrap_or("").trim())
+            .collect();
+        merged += tx.execute(&insert_sql, rusqlite::params_from_iter(values.into_iter()))?;
+        rows_in_batch += 1;
+
+        if rows_in_batch >= batch_size {
+            tx.commit()?;
+            tx = conn.transaction()?;
+            rows_in_batch = 0;
+        }
+    }
+
+    tx.commit()?;
+    Ok(merged)
+}